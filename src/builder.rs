@@ -0,0 +1,121 @@
+//! Builder that bundles analyzer settings into one reusable config.
+
+use sha2::{Digest, Sha256};
+use sudachi::analysis::Mode;
+use sudachi::analysis::stateless_tokenizer::DictionaryAccess;
+use tantivy::tokenizer::TextAnalyzer;
+
+use crate::{IndexingMode, SudachiTokenizer, TokenText, default_pos_filter_rules};
+
+/// Bundles the split mode, token-text selection, and POS stop rules into a
+/// single configuration, and produces a configured `SudachiTokenizer` /
+/// `TextAnalyzer`. The builder itself does not implement serialization.
+/// `config_hash()` lets a host
+/// application detect when an index must be rebuilt because the analyzer
+/// settings changed.
+#[derive(Debug, Clone)]
+pub struct SudachiAnalyzerBuilder {
+    mode: Mode,
+    indexing_mode: IndexingMode,
+    token_text: TokenText,
+    pos_filter_rules: Vec<Vec<String>>,
+}
+
+impl Default for SudachiAnalyzerBuilder {
+    fn default() -> Self {
+        Self {
+            mode: Mode::A,
+            indexing_mode: IndexingMode::default(),
+            token_text: TokenText::default(),
+            pos_filter_rules: default_pos_filter_rules(),
+        }
+    }
+}
+
+impl SudachiAnalyzerBuilder {
+    /// Creates a new builder with the tokenizer's default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the Sudachi split mode (A/B/C).
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the indexing mode (single-granularity vs. stacked tokens).
+    pub fn indexing_mode(mut self, indexing_mode: IndexingMode) -> Self {
+        self.indexing_mode = indexing_mode;
+        self
+    }
+
+    /// Selects which morpheme form is emitted as the token text.
+    pub fn token_text(mut self, token_text: TokenText) -> Self {
+        self.token_text = token_text;
+        self
+    }
+
+    /// Replaces the part-of-speech stop rules.
+    pub fn pos_filter_rules(mut self, pos_filter_rules: Vec<Vec<String>>) -> Self {
+        self.pos_filter_rules = pos_filter_rules;
+        self
+    }
+
+    /// Builds a `SudachiTokenizer` configured per this builder.
+    pub fn build_tokenizer<D: DictionaryAccess>(&self, dict: D) -> SudachiTokenizer<D> {
+        let mut tokenizer = SudachiTokenizer::with_mode(dict, self.mode);
+        tokenizer.set_indexing_mode(self.indexing_mode);
+        tokenizer.set_token_text(self.token_text);
+        tokenizer.set_pos_filter_rules(self.pos_filter_rules.clone());
+        tokenizer
+    }
+
+    /// Builds a `TextAnalyzer` wrapping a `SudachiTokenizer` configured per
+    /// this builder.
+    pub fn build<D: DictionaryAccess + 'static + Send + Sync + Clone>(&self, dict: D) -> TextAnalyzer {
+        TextAnalyzer::from(self.build_tokenizer(dict))
+    }
+
+    /// Returns a stable SHA-256 digest over this configuration, so a host
+    /// application can detect when an index must be rebuilt because the
+    /// analyzer settings changed.
+    pub fn config_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update([mode_byte(self.mode)]);
+        hasher.update([indexing_mode_byte(self.indexing_mode)]);
+        hasher.update([token_text_byte(self.token_text)]);
+        for rule in &self.pos_filter_rules {
+            for pos in rule {
+                hasher.update(pos.as_bytes());
+                hasher.update([0]);
+            }
+            hasher.update([0xff]);
+        }
+        hasher.finalize().into()
+    }
+}
+
+fn mode_byte(mode: Mode) -> u8 {
+    match mode {
+        Mode::A => 0,
+        Mode::B => 1,
+        Mode::C => 2,
+    }
+}
+
+fn indexing_mode_byte(indexing_mode: IndexingMode) -> u8 {
+    match indexing_mode {
+        IndexingMode::Normal => 0,
+        IndexingMode::MultiGranularity => 1,
+    }
+}
+
+fn token_text_byte(token_text: TokenText) -> u8 {
+    match token_text {
+        TokenText::Surface => 0,
+        TokenText::Normalized => 1,
+        TokenText::Dictionary => 2,
+        TokenText::Reading => 3,
+    }
+}
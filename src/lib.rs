@@ -1,18 +1,65 @@
 //! Sudachi tokenizer for Tantivy.
 
+mod builder;
+
+pub use builder::SudachiAnalyzerBuilder;
+
+use std::collections::VecDeque;
 use std::str;
 
 use sudachi::analysis::Mode;
 use sudachi::analysis::stateful_tokenizer::StatefulTokenizer;
 use sudachi::analysis::stateless_tokenizer::DictionaryAccess;
-use sudachi::prelude::MorphemeList;
+use sudachi::prelude::{Morpheme, MorphemeList};
 use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
 
+/// Controls how many tokens `SudachiTokenStream` emits per morpheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IndexingMode {
+    /// One token per morpheme, using the tokenizer's configured split mode.
+    #[default]
+    Normal,
+    /// Tokenize at `Mode::C` and additionally stack each coarse morpheme's
+    /// `Mode::A` sub-units at overlapping positions, so phrase queries can
+    /// match either the whole compound or its components.
+    MultiGranularity,
+}
+
+/// A sub-token awaiting emission after its coarse morpheme.
+struct PendingToken {
+    text: String,
+    offset_from: usize,
+    offset_to: usize,
+}
+
+/// Selects which form of a morpheme is used as the token text. Offsets
+/// always stay anchored to the original surface bytes regardless of this
+/// setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenText {
+    /// The text as it appears in the input.
+    #[default]
+    Surface,
+    /// Sudachi's normalized form, which unifies spelling and conjugation
+    /// variants (e.g. 打込む/打ち込む, full-width/half-width, カナ/かな).
+    Normalized,
+    /// The dictionary's canonical (lemma) form.
+    Dictionary,
+    /// The katakana reading, for phonetic search that matches homophones
+    /// and kanji/kana spelling differences (e.g. はし matches 橋 and 端).
+    /// Morphemes with an empty reading are skipped rather than emitted as
+    /// blank tokens.
+    Reading,
+}
+
 /// Tokenize the text using Sudachi.
 pub struct SudachiTokenizer<D: DictionaryAccess> {
     token: Token,
     stateful_tokenizer: StatefulTokenizer<D>,
     debug: bool,
+    indexing_mode: IndexingMode,
+    token_text: TokenText,
+    pos_filter_rules: Vec<Vec<String>>,
 }
 
 /// TokenStream produced by SudachiTokenizer.
@@ -20,15 +67,28 @@ pub struct SudachiTokenStream<'a, D: DictionaryAccess> {
     token: &'a mut Token,
     morphemes: MorphemeList<D>,
     index: usize,
+    indexing_mode: IndexingMode,
+    token_text: TokenText,
+    pos_filter_rules: &'a [Vec<String>],
+    pending: VecDeque<PendingToken>,
 }
 
 impl<D: DictionaryAccess> SudachiTokenizer<D> {
-    /// Creates a new SudachiTokenizer.
+    /// Creates a new SudachiTokenizer using split Mode::A.
     pub fn new(dict: D) -> Self {
+        Self::with_mode(dict, Mode::A)
+    }
+
+    /// Creates a new SudachiTokenizer using the given Sudachi split mode
+    /// (`Mode::A` for shortest units, `Mode::C` for longest compounds).
+    pub fn with_mode(dict: D, mode: Mode) -> Self {
         Self {
             token: Token::default(),
-            stateful_tokenizer: StatefulTokenizer::new(dict, Mode::A),
+            stateful_tokenizer: StatefulTokenizer::new(dict, mode),
             debug: false,
+            indexing_mode: IndexingMode::default(),
+            token_text: TokenText::default(),
+            pos_filter_rules: default_pos_filter_rules(),
         }
     }
 
@@ -37,6 +97,59 @@ impl<D: DictionaryAccess> SudachiTokenizer<D> {
         self.stateful_tokenizer.set_debug(debug);
         self
     }
+
+    /// Changes the Sudachi split mode, rebuilding the underlying tokenizer.
+    pub fn set_mode(&mut self, mode: Mode) -> &Self {
+        let mut stateful_tokenizer = StatefulTokenizer::new(self.stateful_tokenizer.dict_clone(), mode);
+        stateful_tokenizer.set_debug(self.debug);
+        self.stateful_tokenizer = stateful_tokenizer;
+        self
+    }
+
+    /// Sets the indexing mode. `MultiGranularity` requires split `Mode::C`,
+    /// so enabling it overrides any previously configured split mode.
+    pub fn set_indexing_mode(&mut self, indexing_mode: IndexingMode) -> &Self {
+        self.indexing_mode = indexing_mode;
+        if indexing_mode == IndexingMode::MultiGranularity {
+            self.set_mode(Mode::C);
+        }
+        self
+    }
+
+    /// Selects which morpheme form is emitted as the token text.
+    pub fn set_token_text(&mut self, token_text: TokenText) -> &Self {
+        self.token_text = token_text;
+        self
+    }
+
+    /// Replaces the part-of-speech stop rules. A morpheme is dropped when
+    /// its `part_of_speech()` slice starts with any of the given prefixes,
+    /// e.g. `vec!["助詞".to_string()]` drops all particles.
+    pub fn set_pos_filter_rules(&mut self, pos_filter_rules: Vec<Vec<String>>) -> &Self {
+        self.pos_filter_rules = pos_filter_rules;
+        self
+    }
+}
+
+/// The default POS stop rule, matching the tokenizer's original behavior of
+/// dropping whitespace morphemes.
+pub(crate) fn default_pos_filter_rules() -> Vec<Vec<String>> {
+    vec![vec!["空白".to_string()]]
+}
+
+/// Resolves the configured `TokenText` form for a morpheme. Returns `None`
+/// for `TokenText::Reading` when the morpheme has no reading, so callers
+/// can skip it instead of emitting a blank token.
+fn select_token_text<D: DictionaryAccess>(token_text: TokenText, m: &Morpheme<D>) -> Option<&str> {
+    match token_text {
+        TokenText::Surface => Some(m.surface().as_ref()),
+        TokenText::Normalized => Some(m.normalized_form().as_ref()),
+        TokenText::Dictionary => Some(m.dictionary_form().as_ref()),
+        TokenText::Reading => {
+            let reading = m.reading_form().as_ref();
+            if reading.is_empty() { None } else { Some(reading) }
+        }
+    }
 }
 
 impl<D: DictionaryAccess + Clone> Clone for SudachiTokenizer<D> {
@@ -50,6 +163,9 @@ impl<D: DictionaryAccess + Clone> Clone for SudachiTokenizer<D> {
             token: Token::default(),
             stateful_tokenizer,
             debug: self.debug,
+            indexing_mode: self.indexing_mode,
+            token_text: self.token_text,
+            pos_filter_rules: self.pos_filter_rules.clone(),
         }
     }
 }
@@ -72,37 +188,101 @@ impl<D: DictionaryAccess + 'static + Send + Sync + Clone> Tokenizer for SudachiT
             Err(e) => eprintln!("Tokenization failed, text: {}, error: {}", text, e),
         };
 
-        SudachiTokenStream::new(&mut self.token, morphemes)
+        SudachiTokenStream::new(
+            &mut self.token,
+            morphemes,
+            self.indexing_mode,
+            self.token_text,
+            &self.pos_filter_rules,
+        )
     }
 }
 
 impl<'a, D: DictionaryAccess> SudachiTokenStream<'a, D> {
     /// Creates a new `SudachiTokenStream.`
-    pub fn new(token: &'a mut Token, morphemes: MorphemeList<D>) -> Self {
+    pub fn new(
+        token: &'a mut Token,
+        morphemes: MorphemeList<D>,
+        indexing_mode: IndexingMode,
+        token_text: TokenText,
+        pos_filter_rules: &'a [Vec<String>],
+    ) -> Self {
         Self {
             token,
             morphemes,
             index: 0,
+            indexing_mode,
+            token_text,
+            pos_filter_rules,
+            pending: VecDeque::new(),
         }
     }
 }
 
 impl<'a, D: DictionaryAccess> TokenStream for SudachiTokenStream<'a, D> {
     fn advance(&mut self) -> bool {
+        if let Some(pending) = self.pending.pop_front() {
+            self.token.position = self.token.position.wrapping_add(1);
+            self.token.position_length = 1;
+            self.token.offset_from = pending.offset_from;
+            self.token.offset_to = pending.offset_to;
+            self.token.text.clear();
+            self.token.text.push_str(&pending.text);
+
+            return true;
+        }
+
         while self.index < self.morphemes.len() {
             let m = self.morphemes.get(self.index);
             self.index += 1;
-            if let Some(pos) = m.part_of_speech().get(0)
-                && pos == "空白"
+            let pos = m.part_of_speech();
+            if self
+                .pos_filter_rules
+                .iter()
+                .any(|rule| pos.starts_with(rule.as_slice()))
             {
                 continue;
             }
 
+            let text = match select_token_text(self.token_text, &m) {
+                Some(text) => text,
+                None => continue,
+            };
+
             self.token.position = self.token.position.wrapping_add(1);
+            self.token.position_length = 1;
             self.token.offset_from = m.begin();
             self.token.offset_to = m.end() + 1;
             self.token.text.clear();
-            self.token.text.push_str(m.surface().as_ref());
+            self.token.text.push_str(text);
+
+            if self.indexing_mode == IndexingMode::MultiGranularity
+                && let Ok(sub_morphemes) = m.split(Mode::A)
+                && sub_morphemes.len() > 1
+            {
+                let mut sub_tokens = Vec::with_capacity(sub_morphemes.len());
+                for i in 0..sub_morphemes.len() {
+                    let sub = sub_morphemes.get(i);
+                    if self
+                        .pos_filter_rules
+                        .iter()
+                        .any(|rule| sub.part_of_speech().starts_with(rule.as_slice()))
+                    {
+                        continue;
+                    }
+                    if let Some(text) = select_token_text(self.token_text, &sub) {
+                        sub_tokens.push(PendingToken {
+                            text: text.to_string(),
+                            offset_from: sub.begin(),
+                            offset_to: sub.end() + 1,
+                        });
+                    }
+                }
+                if !sub_tokens.is_empty() {
+                    self.token.position_length = sub_tokens.len();
+                    self.pending.extend(sub_tokens);
+                }
+            }
 
             return true;
         }
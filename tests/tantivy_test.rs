@@ -1,12 +1,13 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use sudachi::analysis::Mode;
 use sudachi::config::Config;
 use sudachi::dic::dictionary::JapaneseDictionary;
 use tantivy::tokenizer::TextAnalyzer;
 use tantivy_tokenizer_api::{Token, TokenStream};
 
-use sudachi_tantivy::SudachiTokenizer;
+use sudachi_tantivy::{IndexingMode, SudachiAnalyzerBuilder, SudachiTokenizer, TokenText};
 
 #[test]
 fn test_tokenize() {
@@ -42,9 +43,120 @@ fn test_mix_jp_alphabet() {
     assert_eq!(tokens[15].text, "。");
 }
 
+#[test]
+fn test_with_mode_c_merges_compound() {
+    let mut analyzer = TextAnalyzer::from(SudachiTokenizer::with_mode(test_dict(), Mode::C));
+    let tokens = collect_tokens(&mut analyzer, "選挙管理委員会");
+
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].text, "選挙管理委員会");
+}
+
+#[test]
+fn test_multi_granularity_stacks_sub_tokens() {
+    let mut tokenizer = SudachiTokenizer::new(test_dict());
+    tokenizer.set_indexing_mode(IndexingMode::MultiGranularity);
+    let mut analyzer = TextAnalyzer::from(tokenizer);
+    let tokens = collect_tokens(&mut analyzer, "選挙管理委員会");
+
+    assert_eq!(tokens.len(), 5);
+    assert_eq!(tokens[0].text, "選挙管理委員会");
+    assert_eq!(tokens[0].position_length, 4);
+    assert_eq!(tokens[1].text, "選挙");
+    assert_eq!(tokens[1].position_length, 1);
+    assert_eq!(tokens[2].text, "管理");
+    assert_eq!(tokens[3].text, "委員");
+    assert_eq!(tokens[4].text, "会");
+}
+
+#[test]
+fn test_token_text_normalized_keeps_surface_offsets() {
+    let mut tokenizer = SudachiTokenizer::new(test_dict());
+    tokenizer.set_token_text(TokenText::Normalized);
+    let mut analyzer = TextAnalyzer::from(tokenizer);
+    let tokens = collect_tokens(&mut analyzer, "Sudachi");
+
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].text, "sudachi");
+    assert_eq!(tokens[0].offset_from, 0);
+    assert_eq!(tokens[0].offset_to, "Sudachi".len());
+}
+
+#[test]
+fn test_pos_filter_drops_configured_particles() {
+    let mut tokenizer = SudachiTokenizer::new(test_dict());
+    tokenizer.set_pos_filter_rules(vec![vec!["助詞".to_string()]]);
+    let mut analyzer = TextAnalyzer::from(tokenizer);
+    let tokens = collect_tokens(&mut analyzer, "日本語の本");
+
+    assert!(tokens.iter().all(|t| t.text != "の"));
+}
+
+#[test]
+fn test_multi_granularity_with_pos_filter_keeps_remaining_sub_tokens() {
+    let mut tokenizer = SudachiTokenizer::new(test_dict());
+    tokenizer.set_indexing_mode(IndexingMode::MultiGranularity);
+    tokenizer.set_pos_filter_rules(vec![vec!["助詞".to_string()]]);
+    let mut analyzer = TextAnalyzer::from(tokenizer);
+    let tokens = collect_tokens(&mut analyzer, "選挙管理委員会");
+
+    assert_eq!(tokens.len(), 5);
+    assert_eq!(tokens[1].text, "選挙");
+    assert_eq!(tokens[2].text, "管理");
+    assert_eq!(tokens[3].text, "委員");
+    assert_eq!(tokens[4].text, "会");
+}
+
+#[test]
+fn test_reading_form_emits_katakana_reading() {
+    let mut tokenizer = SudachiTokenizer::new(test_dict());
+    tokenizer.set_token_text(TokenText::Reading);
+    let mut analyzer = TextAnalyzer::from(tokenizer);
+    let tokens = collect_tokens(&mut analyzer, "選挙管理委員会");
+
+    assert_eq!(tokens.len(), 4);
+    assert_eq!(tokens[0].text, "センキョ");
+    assert_eq!(tokens[1].text, "カンリ");
+    assert_eq!(tokens[2].text, "イイン");
+    assert_eq!(tokens[3].text, "カイ");
+}
+
+#[test]
+fn test_multi_granularity_with_reading_keeps_all_sub_tokens() {
+    let mut tokenizer = SudachiTokenizer::new(test_dict());
+    tokenizer.set_indexing_mode(IndexingMode::MultiGranularity);
+    tokenizer.set_token_text(TokenText::Reading);
+    let mut analyzer = TextAnalyzer::from(tokenizer);
+    let tokens = collect_tokens(&mut analyzer, "選挙管理委員会");
+
+    assert_eq!(tokens.len(), 5);
+    assert_eq!(tokens[0].position_length, 4);
+    assert_eq!(tokens[1].text, "センキョ");
+    assert_eq!(tokens[2].text, "カンリ");
+    assert_eq!(tokens[3].text, "イイン");
+    assert_eq!(tokens[4].text, "カイ");
+}
+
+#[test]
+fn test_builder_builds_configured_analyzer_and_changing_hash() {
+    let base = SudachiAnalyzerBuilder::new();
+    let multi_granularity = SudachiAnalyzerBuilder::new().indexing_mode(IndexingMode::MultiGranularity);
+
+    assert_ne!(base.config_hash(), multi_granularity.config_hash());
+    assert_eq!(base.config_hash(), SudachiAnalyzerBuilder::new().config_hash());
+
+    let mut analyzer = base.build(test_dict());
+    let tokens = collect_tokens(&mut analyzer, "選挙管理委員会");
+
+    assert_eq!(tokens.len(), 4);
+    assert_eq!(tokens[0].text, "選挙");
+}
+
 fn token_stream_helper(text: &str) -> Vec<Token> {
-    let mut analyzer = analyzer();
+    collect_tokens(&mut analyzer(), text)
+}
 
+fn collect_tokens(analyzer: &mut TextAnalyzer, text: &str) -> Vec<Token> {
     let mut token_stream = analyzer.token_stream(text);
     let mut tokens: Vec<Token> = vec![];
     let mut add_token = |token: &Token| {
@@ -55,6 +167,10 @@ fn token_stream_helper(text: &str) -> Vec<Token> {
 }
 
 fn analyzer() -> TextAnalyzer {
+    TextAnalyzer::from(SudachiTokenizer::new(test_dict()))
+}
+
+fn test_dict() -> Arc<JapaneseDictionary> {
     let dict_path = std::env::var("SUDACHI_DICT_PATH")
         .map(|p| PathBuf::from(p))
         .expect("Environemt variable SUDACHI_DICT_PATH is not defined");
@@ -63,7 +179,5 @@ fn analyzer() -> TextAnalyzer {
     let jp_dict = JapaneseDictionary::from_cfg(&config)
         .unwrap_or_else(|e| panic!("Failed to create dictionary: {:?}", e));
 
-    let dict = Arc::new(jp_dict);
-    let tokenizer = SudachiTokenizer::new(dict);
-    TextAnalyzer::from(tokenizer)
+    Arc::new(jp_dict)
 }